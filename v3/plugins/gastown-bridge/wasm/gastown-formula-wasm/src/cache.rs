@@ -0,0 +1,267 @@
+//! Bounded LRU Cache
+//!
+//! Backs `parse_formula` and `cook_formula` with a capacity-bounded
+//! memoization layer keyed by a hash of their raw inputs, so repeated
+//! calls with identical content skip the TOML parsing and serde work
+//! (a `parse_formula` hit still turns the cached JSON into a JS object).
+//! Capacity 0 disables caching without changing results.
+
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+const DEFAULT_CAPACITY: usize = 128;
+
+/// A cached value plus the exact input it was computed from. The hash is
+/// only an index into `entries`; `key_parts` is compared byte-for-byte
+/// on every lookup so a hash collision can never hand back a different
+/// request's result.
+struct CacheEntry {
+    key_parts: Vec<String>,
+    value: String,
+}
+
+struct LruCache {
+    capacity: usize,
+    entries: HashMap<u64, CacheEntry>,
+    // Least-recently-used key at the front, most-recently-used at the back.
+    order: Vec<u64>,
+    hits: u64,
+    misses: u64,
+}
+
+impl LruCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: Vec::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    fn get(&mut self, hash: u64, key_parts: &[&str]) -> Option<String> {
+        if self.capacity == 0 {
+            return None;
+        }
+        let hit = self
+            .entries
+            .get(&hash)
+            .filter(|entry| entry.key_parts.iter().map(String::as_str).eq(key_parts.iter().copied()))
+            .map(|entry| entry.value.clone());
+
+        match hit {
+            Some(value) => {
+                self.hits += 1;
+                self.touch(hash);
+                Some(value)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn put(&mut self, hash: u64, key_parts: Vec<String>, value: String) {
+        if self.capacity == 0 {
+            return;
+        }
+        if !self.entries.contains_key(&hash) && self.entries.len() >= self.capacity && !self.order.is_empty() {
+            let lru_key = self.order.remove(0);
+            self.entries.remove(&lru_key);
+        }
+        self.entries.insert(hash, CacheEntry { key_parts, value });
+        self.touch(hash);
+    }
+
+    fn touch(&mut self, hash: u64) {
+        self.order.retain(|&k| k != hash);
+        self.order.push(hash);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity && !self.order.is_empty() {
+            let lru_key = self.order.remove(0);
+            self.entries.remove(&lru_key);
+        }
+    }
+}
+
+thread_local! {
+    static PARSE_CACHE: RefCell<LruCache> = RefCell::new(LruCache::new(DEFAULT_CAPACITY));
+    static COOK_CACHE: RefCell<LruCache> = RefCell::new(LruCache::new(DEFAULT_CAPACITY));
+}
+
+fn hash_one(a: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    a.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn hash_pair(a: &str, b: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    a.hash(&mut hasher);
+    b.hash(&mut hasher);
+    hasher.finish()
+}
+
+pub fn parse_cache_get(content: &str) -> Option<String> {
+    PARSE_CACHE.with(|c| c.borrow_mut().get(hash_one(content), &[content]))
+}
+
+pub fn parse_cache_put(content: &str, value: String) {
+    PARSE_CACHE.with(|c| c.borrow_mut().put(hash_one(content), vec![content.to_string()], value));
+}
+
+pub fn cook_cache_get(formula_json: &str, vars_json: &str) -> Option<String> {
+    COOK_CACHE.with(|c| c.borrow_mut().get(hash_pair(formula_json, vars_json), &[formula_json, vars_json]))
+}
+
+pub fn cook_cache_put(formula_json: &str, vars_json: &str, value: String) {
+    COOK_CACHE.with(|c| {
+        c.borrow_mut()
+            .put(hash_pair(formula_json, vars_json), vec![formula_json.to_string(), vars_json.to_string()], value)
+    });
+}
+
+/// Set the capacity of both the parse and cook caches. Capacity 0
+/// disables caching entirely.
+pub fn set_cache_capacity_impl(n: usize) {
+    PARSE_CACHE.with(|c| c.borrow_mut().set_capacity(n));
+    COOK_CACHE.with(|c| c.borrow_mut().set_capacity(n));
+}
+
+/// Clear both caches and reset their hit/miss counters.
+pub fn clear_cache_impl() {
+    PARSE_CACHE.with(|c| c.borrow_mut().clear());
+    COOK_CACHE.with(|c| c.borrow_mut().clear());
+}
+
+#[derive(serde::Serialize)]
+struct CacheStats {
+    capacity: usize,
+    parse_hits: u64,
+    parse_misses: u64,
+    cook_hits: u64,
+    cook_misses: u64,
+}
+
+/// Report hit/miss counts for both caches and the current shared
+/// capacity, as a JSON string.
+pub fn cache_stats_impl() -> String {
+    let (parse_hits, parse_misses, capacity) = PARSE_CACHE.with(|c| {
+        let c = c.borrow();
+        (c.hits, c.misses, c.capacity)
+    });
+    let (cook_hits, cook_misses) = COOK_CACHE.with(|c| {
+        let c = c.borrow();
+        (c.hits, c.misses)
+    });
+    let stats = CacheStats {
+        capacity,
+        parse_hits,
+        parse_misses,
+        cook_hits,
+        cook_misses,
+    };
+    serde_json::to_string(&stats).unwrap_or_else(|_| "{}".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lru_evicts_least_recently_used_entry() {
+        let mut cache = LruCache::new(2);
+        cache.put(1, vec!["a".to_string()], "A".to_string());
+        cache.put(2, vec!["b".to_string()], "B".to_string());
+        cache.put(3, vec!["c".to_string()], "C".to_string());
+
+        assert_eq!(cache.get(1, &["a"]), None);
+        assert_eq!(cache.get(2, &["b"]), Some("B".to_string()));
+        assert_eq!(cache.get(3, &["c"]), Some("C".to_string()));
+    }
+
+    #[test]
+    fn lru_touch_on_get_protects_from_eviction() {
+        let mut cache = LruCache::new(2);
+        cache.put(1, vec!["a".to_string()], "A".to_string());
+        cache.put(2, vec!["b".to_string()], "B".to_string());
+        // Touch "a" so "b" becomes the least-recently-used entry.
+        assert_eq!(cache.get(1, &["a"]), Some("A".to_string()));
+        cache.put(3, vec!["c".to_string()], "C".to_string());
+
+        assert_eq!(cache.get(2, &["b"]), None);
+        assert_eq!(cache.get(1, &["a"]), Some("A".to_string()));
+        assert_eq!(cache.get(3, &["c"]), Some("C".to_string()));
+    }
+
+    #[test]
+    fn lru_capacity_zero_disables_caching() {
+        let mut cache = LruCache::new(0);
+        cache.put(1, vec!["a".to_string()], "A".to_string());
+        assert_eq!(cache.get(1, &["a"]), None);
+    }
+
+    #[test]
+    fn lru_hash_collision_falls_through_instead_of_returning_wrong_value() {
+        // Same hash bucket, different actual key material: must miss
+        // rather than hand back the other entry's value.
+        let mut cache = LruCache::new(4);
+        cache.put(42, vec!["formula-a".to_string(), "vars-a".to_string()], "cooked-a".to_string());
+
+        assert_eq!(cache.get(42, &["formula-b", "vars-b"]), None);
+        assert_eq!(cache.get(42, &["formula-a", "vars-a"]), Some("cooked-a".to_string()));
+    }
+
+    #[test]
+    fn lru_set_capacity_evicts_down_to_new_size() {
+        let mut cache = LruCache::new(3);
+        cache.put(1, vec!["a".to_string()], "A".to_string());
+        cache.put(2, vec!["b".to_string()], "B".to_string());
+        cache.put(3, vec!["c".to_string()], "C".to_string());
+
+        cache.set_capacity(1);
+
+        assert_eq!(cache.get(1, &["a"]), None);
+        assert_eq!(cache.get(2, &["b"]), None);
+        assert_eq!(cache.get(3, &["c"]), Some("C".to_string()));
+    }
+
+    #[test]
+    fn parse_cache_roundtrip_and_stats() {
+        clear_cache_impl();
+        set_cache_capacity_impl(8);
+
+        assert_eq!(parse_cache_get("formula-x"), None);
+        parse_cache_put("formula-x", "parsed-x".to_string());
+        assert_eq!(parse_cache_get("formula-x"), Some("parsed-x".to_string()));
+
+        let stats: serde_json::Value = serde_json::from_str(&cache_stats_impl()).unwrap();
+        assert_eq!(stats["parse_hits"], 1);
+        assert_eq!(stats["parse_misses"], 1);
+    }
+
+    #[test]
+    fn cook_cache_distinguishes_inputs_differing_by_one_byte() {
+        clear_cache_impl();
+        set_cache_capacity_impl(8);
+
+        cook_cache_put("{\"formula\":1}", "{\"env\":\"dev\"}", "cooked-dev".to_string());
+        assert_eq!(cook_cache_get("{\"formula\":1}", "{\"env\":\"dev\"}"), Some("cooked-dev".to_string()));
+        // A single byte different in vars_json must not hit the cache.
+        assert_eq!(cook_cache_get("{\"formula\":1}", "{\"env\":\"dev!\"}"), None);
+    }
+}
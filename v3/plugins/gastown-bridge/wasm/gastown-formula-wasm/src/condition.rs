@@ -0,0 +1,225 @@
+//! Guard Expression Evaluation
+//!
+//! Evaluates the small boolean expressions used by `Step.when` and
+//! `Leg.when` guards against a formula's cooked variables, and prunes
+//! guarded-off steps/legs from a cooked formula.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{Leg, Step};
+
+/// Evaluate a guard expression against cooked variable values.
+///
+/// Supports `==`, `!=`, `<`, `>` comparisons against a literal, and bare
+/// truthiness of a single variable name (true unless its value is empty,
+/// `"false"`, or `"0"`). An undefined variable short-circuits to false,
+/// since that's the safe default for a guard with no default value.
+pub fn evaluate(expr: &str, vars: &HashMap<String, String>) -> bool {
+    let expr = expr.trim();
+
+    for op in ["==", "!=", "<", ">"] {
+        if let Some(idx) = expr.find(op) {
+            let lhs = expr[..idx].trim();
+            let rhs = expr[idx + op.len()..].trim().trim_matches('"').trim_matches('\'');
+            let lhs_value = match vars.get(lhs) {
+                Some(v) => v,
+                None => return false,
+            };
+            return match op {
+                "==" => lhs_value == rhs,
+                "!=" => lhs_value != rhs,
+                "<" => compare_numeric(lhs_value, rhs, |a, b| a < b),
+                ">" => compare_numeric(lhs_value, rhs, |a, b| a > b),
+                _ => unreachable!(),
+            };
+        }
+    }
+
+    // Bare truthiness of a single var.
+    match vars.get(expr) {
+        Some(v) => is_truthy(v),
+        None => false,
+    }
+}
+
+fn is_truthy(value: &str) -> bool {
+    !value.is_empty() && value != "false" && value != "0"
+}
+
+fn compare_numeric(lhs: &str, rhs: &str, cmp: impl Fn(f64, f64) -> bool) -> bool {
+    match (lhs.parse::<f64>(), rhs.parse::<f64>()) {
+        (Ok(a), Ok(b)) => cmp(a, b),
+        _ => false,
+    }
+}
+
+/// Drop steps whose `when` guard evaluates to false, rewiring the
+/// `needs` of surviving steps past any removed predecessors so the
+/// dependency chain stays intact.
+pub fn prune_steps(steps: &[Step], vars: &HashMap<String, String>) -> Vec<Step> {
+    let survives: HashMap<String, bool> = steps
+        .iter()
+        .map(|s| {
+            let keep = s.when.as_deref().map(|expr| evaluate(expr, vars)).unwrap_or(true);
+            (s.id.clone(), keep)
+        })
+        .collect();
+    let by_id: HashMap<String, &Step> = steps.iter().map(|s| (s.id.clone(), s)).collect();
+    let mut memo: HashMap<String, Vec<String>> = HashMap::new();
+
+    steps
+        .iter()
+        .filter(|s| *survives.get(&s.id).unwrap_or(&true))
+        .map(|s| {
+            let mut pruned = s.clone();
+            pruned.needs = resolve_needs(&s.id, &by_id, &survives, &mut memo);
+            pruned
+        })
+        .collect()
+}
+
+/// Resolve `start`'s `needs` past any pruned predecessors, walking the
+/// chain iteratively. `on_path` records every pruned step already walked
+/// through from `start` and guards against an unbounded walk when pruned
+/// steps reference each other in a cycle (nothing upstream validates that
+/// `needs` is acyclic): a pruned predecessor already walked through is
+/// skipped rather than revisited.
+fn resolve_needs(
+    start: &str,
+    by_id: &HashMap<String, &Step>,
+    survives: &HashMap<String, bool>,
+    memo: &mut HashMap<String, Vec<String>>,
+) -> Vec<String> {
+    if let Some(cached) = memo.get(start) {
+        return cached.clone();
+    }
+
+    let mut resolved = Vec::new();
+    let mut seen_resolved: HashSet<String> = HashSet::new();
+    let mut on_path: HashSet<String> = HashSet::new();
+    on_path.insert(start.to_string());
+
+    let mut stack: Vec<String> = by_id.get(start).map(|s| s.needs.clone()).unwrap_or_default();
+    while let Some(need) = stack.pop() {
+        if *survives.get(&need).unwrap_or(&true) {
+            if seen_resolved.insert(need.clone()) {
+                resolved.push(need);
+            }
+            continue;
+        }
+        if !on_path.insert(need.clone()) {
+            // Already walked through this pruned step (reached again by
+            // another route, or through a cycle among pruned steps): its
+            // needs are already on the stack, so skip it instead of looping.
+            continue;
+        }
+        if let Some(step) = by_id.get(&need) {
+            stack.extend(step.needs.clone());
+        }
+    }
+
+    memo.insert(start.to_string(), resolved.clone());
+    resolved
+}
+
+/// Drop legs whose `when` guard evaluates to false. Legs have no
+/// dependency edges to rewire (only an optional `order`), so pruning is
+/// a plain filter.
+pub fn prune_legs(legs: &[Leg], vars: &HashMap<String, String>) -> Vec<Leg> {
+    legs.iter()
+        .filter(|l| l.when.as_deref().map(|expr| evaluate(expr, vars)).unwrap_or(true))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn step(id: &str, needs: Vec<&str>, when: Option<&str>) -> Step {
+        Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: id.to_string(),
+            needs: needs.into_iter().map(String::from).collect(),
+            duration: None,
+            requires: Vec::new(),
+            when: when.map(String::from),
+            restart: None,
+            max_retries: None,
+            backoff_ms: None,
+        }
+    }
+
+    #[test]
+    fn evaluate_equality() {
+        let v = vars(&[("env", "prod")]);
+        assert!(evaluate("env == \"prod\"", &v));
+        assert!(!evaluate("env == \"dev\"", &v));
+        assert!(evaluate("env != \"dev\"", &v));
+    }
+
+    #[test]
+    fn evaluate_numeric_comparison() {
+        let v = vars(&[("count", "5")]);
+        assert!(evaluate("count > 3", &v));
+        assert!(!evaluate("count < 3", &v));
+    }
+
+    #[test]
+    fn evaluate_bare_truthiness() {
+        assert!(evaluate("tests_enabled", &vars(&[("tests_enabled", "true")])));
+        assert!(!evaluate("tests_enabled", &vars(&[("tests_enabled", "false")])));
+        assert!(!evaluate("tests_enabled", &vars(&[("tests_enabled", "0")])));
+    }
+
+    #[test]
+    fn evaluate_undefined_var_is_false() {
+        assert!(!evaluate("missing == \"x\"", &HashMap::new()));
+        assert!(!evaluate("missing", &HashMap::new()));
+    }
+
+    #[test]
+    fn prune_steps_drops_false_guard_and_rewires_needs() {
+        let steps = vec![
+            step("a", vec![], None),
+            step("b", vec!["a"], Some("env == \"prod\"")),
+            step("c", vec!["b"], None),
+        ];
+        let pruned = prune_steps(&steps, &vars(&[("env", "dev")]));
+
+        let ids: Vec<&str> = pruned.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "c"]);
+
+        let c = pruned.iter().find(|s| s.id == "c").unwrap();
+        assert_eq!(c.needs, vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn prune_steps_keeps_true_guard() {
+        let steps = vec![step("a", vec![], Some("env == \"prod\""))];
+        let pruned = prune_steps(&steps, &vars(&[("env", "prod")]));
+        assert_eq!(pruned.len(), 1);
+    }
+
+    #[test]
+    fn prune_steps_handles_cycle_among_pruned_steps_without_overflow() {
+        // a pruned, needs b; b pruned, needs a; c survives, needs a.
+        let steps = vec![
+            step("a", vec!["b"], Some("false_guard")),
+            step("b", vec!["a"], Some("false_guard")),
+            step("c", vec!["a"], None),
+        ];
+        let pruned = prune_steps(&steps, &HashMap::new());
+
+        let ids: Vec<&str> = pruned.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["c"]);
+        // Both "a" and "b" are pruned with no default-true guard path
+        // left to rewire onto, so "c" ends up with no surviving needs.
+        assert!(pruned[0].needs.is_empty());
+    }
+}
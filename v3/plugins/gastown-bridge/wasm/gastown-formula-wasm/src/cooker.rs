@@ -0,0 +1,479 @@
+//! Formula Cooking
+//!
+//! Substitutes declared variables into a parsed `Formula`, producing a
+//! `CookedFormula` ready for molecule generation.
+
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use regex::Regex;
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+
+use crate::{cache, condition, CookedFormula, Formula};
+
+/// Current time as an ISO-8601 string for `CookedFormula.cooked_at`.
+///
+/// Backed by `js_sys::Date` on the `wasm32` target this crate actually
+/// ships for. `js_sys`'s imported functions panic when called from a
+/// native (non-wasm32) test binary, so plain `cargo test` gets a
+/// `SystemTime`-based fallback instead, which needs no JS runtime. The
+/// fallback is not a calendar timestamp: it renders the fixed epoch
+/// `1970-01-01T00:00:00.000Z` followed by `+<seconds since epoch>s`.
+#[cfg(target_arch = "wasm32")]
+fn current_timestamp() -> String {
+    js_sys::Date::new_0().to_iso_string().as_string().unwrap_or_default()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn current_timestamp() -> String {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format!("1970-01-01T00:00:00.000Z+{}s", secs)
+}
+
+/// A single variable that failed validation against its declared
+/// `pattern`, `enum`, or `required` constraint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VarError {
+    pub var: String,
+    pub reason: String,
+    pub expected: String,
+}
+
+/// Wrapper returned to JS when one or more variables fail validation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ValidationFailure {
+    errors: Vec<VarError>,
+}
+
+/// Validate `vars` against the constraints declared on `formula.vars`.
+///
+/// Checks, for every declared variable:
+/// - `required` with no `default` and no supplied value -> missing
+/// - a supplied value that does not match `pattern` (as a regex)
+/// - a supplied value that is not a member of `enum_values`
+///
+/// Returns every failure found, not just the first.
+fn validate_vars_against(formula: &Formula, vars: &HashMap<String, String>) -> Vec<VarError> {
+    let mut errors = Vec::new();
+
+    for (name, var) in &formula.vars {
+        let supplied = vars.get(name).or(var.default.as_ref());
+
+        let value = match supplied {
+            Some(value) => value,
+            None => {
+                if var.required {
+                    errors.push(VarError {
+                        var: name.clone(),
+                        reason: "missing".to_string(),
+                        expected: "a value (no default declared)".to_string(),
+                    });
+                }
+                continue;
+            }
+        };
+
+        if let Some(pattern) = &var.pattern {
+            match Regex::new(pattern) {
+                Ok(re) => {
+                    if !re.is_match(value) {
+                        errors.push(VarError {
+                            var: name.clone(),
+                            reason: "pattern_mismatch".to_string(),
+                            expected: pattern.clone(),
+                        });
+                    }
+                }
+                Err(e) => {
+                    errors.push(VarError {
+                        var: name.clone(),
+                        reason: format!("invalid_pattern: {}", e),
+                        expected: pattern.clone(),
+                    });
+                }
+            }
+        }
+
+        if let Some(enum_values) = &var.enum_values {
+            if !enum_values.iter().any(|allowed| allowed == value) {
+                errors.push(VarError {
+                    var: name.clone(),
+                    reason: "not_in_enum".to_string(),
+                    expected: enum_values.join(", "),
+                });
+            }
+        }
+    }
+
+    errors
+}
+
+/// Resolve the final string value for each declared var: the supplied
+/// value if present, otherwise its `default`. Vars with neither are
+/// simply absent from the result (already reported by validation if
+/// they were `required`).
+fn resolve_vars(formula: &Formula, vars: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut cooked = HashMap::new();
+    for (name, var) in &formula.vars {
+        if let Some(value) = vars.get(name) {
+            cooked.insert(name.clone(), value.clone());
+        } else if let Some(default) = &var.default {
+            cooked.insert(name.clone(), default.clone());
+        }
+    }
+    cooked
+}
+
+/// The sorted variable-name signature of a cooked-vars map, used to
+/// decide whether two formulas can share a compiled [`VarAutomaton`].
+///
+/// Built from the *resolved* vars rather than `formula.vars`, so a
+/// declared-but-unsupplied, non-required, no-default var has no
+/// placeholder pattern in the automaton and its `{{name}}` is left as
+/// literal text in the output, matching the pre-Aho-Corasick behavior.
+fn var_signature(cooked_vars: &HashMap<String, String>) -> Vec<String> {
+    let mut names: Vec<String> = cooked_vars.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// A single-pass Aho-Corasick automaton over the `{{name}}` placeholder of
+/// every resolved variable (see [`var_signature`]), with leftmost-longest match
+/// semantics so `{{foo_bar}}` is never shadowed by a `{{foo}}` pattern.
+/// Scans each template string exactly once regardless of variable count.
+struct VarAutomaton {
+    ac: AhoCorasick,
+    names: Vec<String>,
+}
+
+impl VarAutomaton {
+    fn build(names: Vec<String>) -> Result<Self, JsValue> {
+        let patterns: Vec<String> = names.iter().map(|n| format!("{{{{{}}}}}", n)).collect();
+        let ac = AhoCorasickBuilder::new()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(&patterns)
+            .map_err(|e| JsValue::from_str(&format!("Automaton build error: {}", e)))?;
+        Ok(Self { ac, names })
+    }
+
+    /// Replace every placeholder in `text` with the matching entry from
+    /// `cooked_vars` in a single left-to-right scan. `cooked_vars` is
+    /// expected to carry a value for every name this automaton was built
+    /// from (callers sharing an automaton across formulas still pass
+    /// their own `cooked_vars`, just with the same key set).
+    fn apply(&self, text: &str, cooked_vars: &HashMap<String, String>) -> String {
+        let values: Vec<&str> = self
+            .names
+            .iter()
+            .map(|n| cooked_vars.get(n).map(String::as_str).unwrap_or(""))
+            .collect();
+        self.ac.replace_all(text, &values)
+    }
+}
+
+/// Rewrite every templated string on a cooked formula through `automaton`.
+fn substitute_formula(
+    cooked_formula: &mut Formula,
+    automaton: &VarAutomaton,
+    cooked_vars: &HashMap<String, String>,
+) {
+    cooked_formula.description = automaton.apply(&cooked_formula.description, cooked_vars);
+    for leg in &mut cooked_formula.legs {
+        leg.title = automaton.apply(&leg.title, cooked_vars);
+        leg.focus = automaton.apply(&leg.focus, cooked_vars);
+        leg.description = automaton.apply(&leg.description, cooked_vars);
+    }
+    for step in &mut cooked_formula.steps {
+        step.title = automaton.apply(&step.title, cooked_vars);
+        step.description = automaton.apply(&step.description, cooked_vars);
+    }
+    if let Some(synthesis) = &mut cooked_formula.synthesis {
+        if let Some(description) = &synthesis.description {
+            synthesis.description = Some(automaton.apply(description, cooked_vars));
+        }
+    }
+}
+
+/// Cook a single formula, substituting all declared variables.
+///
+/// Memoized by [`cache`] on a hash of `formula_json` + `vars_json`: a
+/// successful cook of the same inputs is returned from cache without
+/// re-validating or re-substituting.
+pub fn cook_formula_impl(formula_json: &str, vars_json: &str) -> Result<String, JsValue> {
+    if let Some(cached) = cache::cook_cache_get(formula_json, vars_json) {
+        return Ok(cached);
+    }
+
+    let formula: Formula = serde_json::from_str(formula_json)
+        .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+    let vars: HashMap<String, String> = serde_json::from_str(vars_json)
+        .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+
+    let errors = validate_vars_against(&formula, &vars);
+    if !errors.is_empty() {
+        let failure = ValidationFailure { errors };
+        let json = serde_json::to_string(&failure)
+            .map_err(|e| JsValue::from_str(&format!("Serialize error: {}", e)))?;
+        return Err(JsValue::from_str(&json));
+    }
+
+    let cooked_vars = resolve_vars(&formula, &vars);
+    let automaton = VarAutomaton::build(var_signature(&cooked_vars))?;
+
+    let mut cooked_formula = formula.clone();
+    cooked_formula.steps = condition::prune_steps(&cooked_formula.steps, &cooked_vars);
+    cooked_formula.legs = condition::prune_legs(&cooked_formula.legs, &cooked_vars);
+    substitute_formula(&mut cooked_formula, &automaton, &cooked_vars);
+
+    let original_name = formula.name.clone();
+    let cooked = CookedFormula {
+        formula: cooked_formula,
+        cooked_at: current_timestamp(),
+        cooked_vars,
+        original_name,
+    };
+
+    let json = serde_json::to_string(&cooked)
+        .map_err(|e| JsValue::from_str(&format!("Serialize error: {}", e)))?;
+    cache::cook_cache_put(formula_json, vars_json, json.clone());
+    Ok(json)
+}
+
+/// Cook a batch of formulas against their respective variable maps.
+///
+/// `formulas_json` and `vars_json` must be JSON arrays of equal length;
+/// the formula at index `i` is cooked with the vars at index `i`. Formulas
+/// whose resolved variables have the same names share one compiled [`VarAutomaton`]
+/// instead of each paying its own construction cost, which keeps the
+/// advertised batch speedup meaningful as the variable count grows.
+pub fn cook_batch_impl(formulas_json: &str, vars_json: &str) -> Result<String, JsValue> {
+    let formulas: Vec<Formula> = serde_json::from_str(formulas_json)
+        .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+    let vars_list: Vec<HashMap<String, String>> = serde_json::from_str(vars_json)
+        .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+
+    if formulas.len() != vars_list.len() {
+        return Err(JsValue::from_str(
+            "formulas_json and vars_json must have the same length",
+        ));
+    }
+
+    let mut automatons: HashMap<Vec<String>, VarAutomaton> = HashMap::new();
+    let mut cooked_all = Vec::with_capacity(formulas.len());
+
+    for (formula, vars) in formulas.iter().zip(vars_list.iter()) {
+        let errors = validate_vars_against(formula, vars);
+        if !errors.is_empty() {
+            let failure = ValidationFailure { errors };
+            let json = serde_json::to_string(&failure)
+                .map_err(|e| JsValue::from_str(&format!("Serialize error: {}", e)))?;
+            return Err(JsValue::from_str(&json));
+        }
+
+        let cooked_vars = resolve_vars(formula, vars);
+        let signature = var_signature(&cooked_vars);
+        if !automatons.contains_key(&signature) {
+            let automaton = VarAutomaton::build(signature.clone())?;
+            automatons.insert(signature.clone(), automaton);
+        }
+        let automaton = automatons.get(&signature).expect("just inserted");
+
+        let mut cooked_formula = formula.clone();
+        cooked_formula.steps = condition::prune_steps(&cooked_formula.steps, &cooked_vars);
+        cooked_formula.legs = condition::prune_legs(&cooked_formula.legs, &cooked_vars);
+        substitute_formula(&mut cooked_formula, automaton, &cooked_vars);
+
+        let cooked = CookedFormula {
+            formula: cooked_formula,
+            cooked_at: current_timestamp(),
+            cooked_vars,
+            original_name: formula.name.clone(),
+        };
+        cooked_all.push(cooked);
+    }
+
+    serde_json::to_string(&cooked_all)
+        .map_err(|e| JsValue::from_str(&format!("Serialize error: {}", e)))
+}
+
+/// Validate `vars_json` against `formula_json`'s declared constraints
+/// without cooking the formula. Returns `{"valid": true}` or
+/// `{"valid": false, "errors": [...]}` as a JSON string.
+pub fn validate_vars_impl(formula_json: &str, vars_json: &str) -> String {
+    let formula: Formula = match serde_json::from_str(formula_json) {
+        Ok(f) => f,
+        Err(e) => {
+            let reason = format!("invalid_formula_json: {}", e);
+            return serde_json::json!({"valid": false, "errors": [{"var": "", "reason": reason, "expected": ""}]}).to_string();
+        }
+    };
+    let vars: HashMap<String, String> = match serde_json::from_str(vars_json) {
+        Ok(v) => v,
+        Err(e) => {
+            let reason = format!("invalid_vars_json: {}", e);
+            return serde_json::json!({"valid": false, "errors": [{"var": "", "reason": reason, "expected": ""}]}).to_string();
+        }
+    };
+
+    let errors = validate_vars_against(&formula, &vars);
+    if errors.is_empty() {
+        r#"{"valid": true}"#.to_string()
+    } else {
+        serde_json::json!({"valid": false, "errors": errors}).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FormulaType, Var};
+
+    fn formula_with_var(name: &str, var: Var) -> Formula {
+        let mut vars = HashMap::new();
+        vars.insert(name.to_string(), var);
+        Formula {
+            name: "test-formula".to_string(),
+            description: "A test formula".to_string(),
+            formula_type: FormulaType::Workflow,
+            version: 1,
+            legs: Vec::new(),
+            synthesis: None,
+            steps: Vec::new(),
+            vars,
+        }
+    }
+
+    fn plain_var() -> Var {
+        Var {
+            name: "v".to_string(),
+            description: None,
+            default: None,
+            required: false,
+            pattern: None,
+            enum_values: None,
+        }
+    }
+
+    #[test]
+    fn validate_vars_against_reports_missing_required() {
+        let formula = formula_with_var("env", Var { required: true, ..plain_var() });
+        let errors = validate_vars_against(&formula, &HashMap::new());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].reason, "missing");
+    }
+
+    #[test]
+    fn validate_vars_against_allows_missing_required_with_default() {
+        let formula = formula_with_var(
+            "env",
+            Var { required: true, default: Some("dev".to_string()), ..plain_var() },
+        );
+        let errors = validate_vars_against(&formula, &HashMap::new());
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn validate_vars_against_reports_pattern_mismatch() {
+        let formula = formula_with_var(
+            "name",
+            Var { pattern: Some("^[a-z]+$".to_string()), ..plain_var() },
+        );
+        let mut vars = HashMap::new();
+        vars.insert("name".to_string(), "NOT-LOWERCASE".to_string());
+        let errors = validate_vars_against(&formula, &vars);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].reason, "pattern_mismatch");
+    }
+
+    #[test]
+    fn validate_vars_against_reports_enum_violation() {
+        let formula = formula_with_var(
+            "env",
+            Var { enum_values: Some(vec!["dev".to_string(), "prod".to_string()]), ..plain_var() },
+        );
+        let mut vars = HashMap::new();
+        vars.insert("env".to_string(), "staging".to_string());
+        let errors = validate_vars_against(&formula, &vars);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].reason, "not_in_enum");
+    }
+
+    #[test]
+    fn validate_vars_against_accepts_valid_value() {
+        let formula = formula_with_var(
+            "env",
+            Var {
+                pattern: Some("^[a-z]+$".to_string()),
+                enum_values: Some(vec!["dev".to_string(), "prod".to_string()]),
+                ..plain_var()
+            },
+        );
+        let mut vars = HashMap::new();
+        vars.insert("env".to_string(), "prod".to_string());
+        let errors = validate_vars_against(&formula, &vars);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn automaton_substitutes_every_placeholder_in_one_pass() {
+        let mut cooked_vars = HashMap::new();
+        cooked_vars.insert("name".to_string(), "Alice".to_string());
+        cooked_vars.insert("name_suffix".to_string(), "Jr".to_string());
+
+        let automaton = VarAutomaton::build(var_signature(&cooked_vars)).unwrap();
+        let result = automaton.apply("Hello {{name}} {{name_suffix}}!", &cooked_vars);
+
+        assert_eq!(result, "Hello Alice Jr!");
+    }
+
+    #[test]
+    fn automaton_leftmost_longest_does_not_shadow_longer_names() {
+        let mut cooked_vars = HashMap::new();
+        cooked_vars.insert("foo".to_string(), "SHORT".to_string());
+        cooked_vars.insert("foo_bar".to_string(), "LONG".to_string());
+
+        let automaton = VarAutomaton::build(var_signature(&cooked_vars)).unwrap();
+        let result = automaton.apply("{{foo_bar}}", &cooked_vars);
+
+        assert_eq!(result, "LONG");
+    }
+
+    #[test]
+    fn unresolved_optional_placeholder_is_left_as_literal_text() {
+        // "greeting" has no supplied value and no default, so it never
+        // enters cooked_vars and must not be blanked out.
+        let cooked_vars = HashMap::new();
+        let automaton = VarAutomaton::build(var_signature(&cooked_vars)).unwrap();
+        let result = automaton.apply("{{greeting}}, world", &cooked_vars);
+
+        assert_eq!(result, "{{greeting}}, world");
+    }
+
+    #[test]
+    fn cook_batch_shares_automaton_across_formulas_with_same_var_set() {
+        let mut formula_a = formula_with_var(
+            "env",
+            Var { required: false, default: Some("dev".to_string()), ..plain_var() },
+        );
+        formula_a.name = "formula-a".to_string();
+        formula_a.description = "running in {{env}}".to_string();
+
+        let mut formula_b = formula_a.clone();
+        formula_b.name = "formula-b".to_string();
+
+        let formulas_json = serde_json::to_string(&vec![formula_a, formula_b]).unwrap();
+        let mut vars_a = HashMap::new();
+        vars_a.insert("env".to_string(), "prod".to_string());
+        let vars_json = serde_json::to_string(&vec![vars_a, HashMap::new()]).unwrap();
+
+        let result = cook_batch_impl(&formulas_json, &vars_json).unwrap();
+        let cooked: Vec<CookedFormula> = serde_json::from_str(&result).unwrap();
+
+        assert_eq!(cooked[0].formula.description, "running in prod");
+        assert_eq!(cooked[1].formula.description, "running in dev");
+    }
+}
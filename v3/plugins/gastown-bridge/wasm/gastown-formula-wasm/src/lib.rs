@@ -20,10 +20,13 @@ use std::collections::HashMap;
 mod parser;
 mod cooker;
 mod molecule;
+mod condition;
+mod cache;
 
 pub use parser::*;
 pub use cooker::*;
 pub use molecule::*;
+pub use condition::*;
 
 // ============================================================================
 // Core Types
@@ -39,6 +42,16 @@ pub enum FormulaType {
     Aspect,
 }
 
+/// Restart behavior for a bead that fails during execution.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartMode {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+}
+
 /// Workflow step definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Step {
@@ -51,6 +64,18 @@ pub struct Step {
     pub duration: Option<u32>,
     #[serde(default)]
     pub requires: Vec<String>,
+    /// Boolean guard over cooked vars (e.g. `env == "prod"`, `count > 3`,
+    /// or bare truthiness of a single var). When present and false, the
+    /// step is pruned from the cooked output and generated molecule.
+    #[serde(default)]
+    pub when: Option<String>,
+    /// Retry behavior if this step's bead fails. Defaults to `Never`.
+    #[serde(default)]
+    pub restart: Option<RestartMode>,
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+    #[serde(default)]
+    pub backoff_ms: Option<u32>,
 }
 
 /// Convoy leg definition
@@ -64,6 +89,17 @@ pub struct Leg {
     pub agent: Option<String>,
     #[serde(default)]
     pub order: Option<u32>,
+    /// Boolean guard over cooked vars; a leg is dropped from the cooked
+    /// output when its guard evaluates to false. See [`Step::when`].
+    #[serde(default)]
+    pub when: Option<String>,
+    /// See [`Step::restart`].
+    #[serde(default)]
+    pub restart: Option<RestartMode>,
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+    #[serde(default)]
+    pub backoff_ms: Option<u32>,
 }
 
 /// Variable definition
@@ -184,6 +220,20 @@ pub fn cook_batch(formulas_json: &str, vars_json: &str) -> Result<String, JsValu
     cooker::cook_batch_impl(formulas_json, vars_json)
 }
 
+/// Validate variables against a formula's declared `pattern`, `enum`, and
+/// `required` constraints without cooking it.
+///
+/// # Arguments
+/// * `formula_json` - Formula as JSON string
+/// * `vars_json` - Variables as JSON string
+///
+/// # Returns
+/// * `String` - `{"valid": true}` or `{"valid": false, "errors": [{var, reason, expected}, ...]}`
+#[wasm_bindgen]
+pub fn validate_vars(formula_json: &str, vars_json: &str) -> String {
+    cooker::validate_vars_impl(formula_json, vars_json)
+}
+
 /// Generate a molecule (bead chain) from a cooked formula
 ///
 /// # Arguments
@@ -220,7 +270,35 @@ pub fn get_formula_type(content: &str) -> Result<String, JsValue> {
     parser::get_formula_type_impl(content)
 }
 
-#[cfg(test)]
+/// Set the capacity of the parse/cook LRU caches. Capacity 0 disables
+/// caching entirely without changing results.
+///
+/// # Arguments
+/// * `n` - Maximum number of entries each cache may hold
+#[wasm_bindgen]
+pub fn set_cache_capacity(n: usize) {
+    cache::set_cache_capacity_impl(n)
+}
+
+/// Clear the parse/cook LRU caches and reset their hit/miss counters.
+#[wasm_bindgen]
+pub fn clear_cache() {
+    cache::clear_cache_impl()
+}
+
+/// Get parse/cook cache hit and miss counts plus the current capacity.
+///
+/// # Returns
+/// * `String` - JSON with `capacity`, `parse_hits`, `parse_misses`, `cook_hits`, `cook_misses`
+#[wasm_bindgen]
+pub fn cache_stats() -> String {
+    cache::cache_stats_impl()
+}
+
+// `parse_formula` crosses into `JsValue`, which wasm-bindgen only backs
+// on an actual wasm32 target; this needs a real or emulated JS host
+// (e.g. `wasm-bindgen-test`) to run, not plain `cargo test`.
+#[cfg(all(test, target_arch = "wasm32"))]
 mod tests {
     use super::*;
 
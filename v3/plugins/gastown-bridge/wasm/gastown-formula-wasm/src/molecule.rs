@@ -0,0 +1,196 @@
+//! Molecule Generation
+//!
+//! Expands a cooked `Formula` into a molecule: an executable bead chain
+//! derived from its `steps` (workflow/expansion/aspect formulas) or
+//! `legs` (convoy formulas).
+
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::{condition, CookedFormula, FormulaType, RestartMode};
+
+/// Restart policy for a bead that fails during execution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartPolicy {
+    pub restart: RestartMode,
+    pub max_retries: u32,
+    pub backoff_ms: u32,
+}
+
+/// A single bead in the generated molecule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoleculeBead {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    #[serde(default)]
+    pub needs: Vec<String>,
+    #[serde(default)]
+    pub duration: Option<u32>,
+    pub policy: RestartPolicy,
+    /// Transitive set of beads that depend on this one through `needs`,
+    /// i.e. the beads that must be held or cancelled if this bead exhausts
+    /// its retries.
+    #[serde(default)]
+    pub blast_radius: Vec<String>,
+}
+
+/// Executable bead chain generated from a cooked formula.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Molecule {
+    pub name: String,
+    pub formula_type: FormulaType,
+    pub beads: Vec<MoleculeBead>,
+}
+
+/// Compute, for every bead, the transitive set of beads it blocks (the
+/// beads reachable by following `needs` edges backwards from it).
+fn compute_blast_radii(beads: &[MoleculeBead]) -> HashMap<String, Vec<String>> {
+    let mut direct_dependents: HashMap<String, Vec<String>> = HashMap::new();
+    for bead in beads {
+        for need in &bead.needs {
+            direct_dependents.entry(need.clone()).or_default().push(bead.id.clone());
+        }
+    }
+
+    let mut radii = HashMap::new();
+    for bead in beads {
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut stack: Vec<String> = direct_dependents.get(&bead.id).cloned().unwrap_or_default();
+        while let Some(id) = stack.pop() {
+            if seen.insert(id.clone()) {
+                if let Some(next) = direct_dependents.get(&id) {
+                    stack.extend(next.clone());
+                }
+            }
+        }
+        let mut blocked: Vec<String> = seen.into_iter().collect();
+        blocked.sort();
+        radii.insert(bead.id.clone(), blocked);
+    }
+    radii
+}
+
+/// Generate a molecule from a cooked formula, applying `when` guards a
+/// second time in case the caller built `formula_json` without going
+/// through `cook_formula`.
+pub fn generate_molecule_impl(formula_json: &str) -> Result<String, JsValue> {
+    let cooked: CookedFormula = serde_json::from_str(formula_json)
+        .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+
+    let mut beads: Vec<MoleculeBead> = match cooked.formula.formula_type {
+        FormulaType::Convoy => {
+            let legs = condition::prune_legs(&cooked.formula.legs, &cooked.cooked_vars);
+            legs.iter()
+                .map(|leg| MoleculeBead {
+                    id: leg.id.clone(),
+                    title: leg.title.clone(),
+                    description: leg.description.clone(),
+                    needs: Vec::new(),
+                    duration: None,
+                    policy: RestartPolicy {
+                        restart: leg.restart.unwrap_or_default(),
+                        max_retries: leg.max_retries.unwrap_or(0),
+                        backoff_ms: leg.backoff_ms.unwrap_or(0),
+                    },
+                    blast_radius: Vec::new(),
+                })
+                .collect()
+        }
+        _ => {
+            let steps = condition::prune_steps(&cooked.formula.steps, &cooked.cooked_vars);
+            steps
+                .iter()
+                .map(|step| MoleculeBead {
+                    id: step.id.clone(),
+                    title: step.title.clone(),
+                    description: step.description.clone(),
+                    needs: step.needs.clone(),
+                    duration: step.duration,
+                    policy: RestartPolicy {
+                        restart: step.restart.unwrap_or_default(),
+                        max_retries: step.max_retries.unwrap_or(0),
+                        backoff_ms: step.backoff_ms.unwrap_or(0),
+                    },
+                    blast_radius: Vec::new(),
+                })
+                .collect()
+        }
+    };
+
+    let radii = compute_blast_radii(&beads);
+    for bead in &mut beads {
+        bead.blast_radius = radii.get(&bead.id).cloned().unwrap_or_default();
+    }
+
+    let molecule = Molecule {
+        name: cooked.formula.name.clone(),
+        formula_type: cooked.formula.formula_type.clone(),
+        beads,
+    };
+
+    serde_json::to_string(&molecule).map_err(|e| JsValue::from_str(&format!("Serialize error: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bead(id: &str, needs: Vec<&str>) -> MoleculeBead {
+        MoleculeBead {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: id.to_string(),
+            needs: needs.into_iter().map(String::from).collect(),
+            duration: None,
+            policy: RestartPolicy {
+                restart: RestartMode::Never,
+                max_retries: 0,
+                backoff_ms: 0,
+            },
+            blast_radius: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn compute_blast_radii_follows_transitive_dependents() {
+        // a <- b <- c (c needs b, b needs a): a's failure blocks b and c.
+        let beads = vec![bead("a", vec![]), bead("b", vec!["a"]), bead("c", vec!["b"])];
+        let radii = compute_blast_radii(&beads);
+
+        assert_eq!(radii["a"], vec!["b".to_string(), "c".to_string()]);
+        assert_eq!(radii["b"], vec!["c".to_string()]);
+        assert!(radii["c"].is_empty());
+    }
+
+    #[test]
+    fn compute_blast_radii_handles_diamond_without_duplicates() {
+        // a <- b, a <- c, {b, c} <- d
+        let beads = vec![
+            bead("a", vec![]),
+            bead("b", vec!["a"]),
+            bead("c", vec!["a"]),
+            bead("d", vec!["b", "c"]),
+        ];
+        let radii = compute_blast_radii(&beads);
+
+        assert_eq!(radii["a"], vec!["b".to_string(), "c".to_string(), "d".to_string()]);
+    }
+
+    #[test]
+    fn compute_blast_radii_independent_beads_have_empty_radius() {
+        let beads = vec![bead("a", vec![]), bead("b", vec![])];
+        let radii = compute_blast_radii(&beads);
+
+        assert!(radii["a"].is_empty());
+        assert!(radii["b"].is_empty());
+    }
+
+    #[test]
+    fn restart_mode_round_trips_through_kebab_case_json() {
+        assert_eq!(serde_json::to_string(&RestartMode::OnFailure).unwrap(), "\"on-failure\"");
+        assert_eq!(serde_json::from_str::<RestartMode>("\"always\"").unwrap(), RestartMode::Always);
+        assert_eq!(RestartMode::default(), RestartMode::Never);
+    }
+}
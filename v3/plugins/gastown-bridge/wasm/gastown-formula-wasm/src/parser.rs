@@ -0,0 +1,48 @@
+//! TOML Formula Parsing
+//!
+//! Parses the TOML formula DSL into a [`crate::Formula`]. Results are
+//! memoized by the bounded LRU cache in [`crate::cache`], keyed on the
+//! raw TOML string, so repeated parses of the same content skip the TOML
+//! parse; each call still builds the JS object from the cached JSON.
+
+use wasm_bindgen::prelude::*;
+
+use crate::cache;
+use crate::{Formula, FormulaType};
+
+/// Parse a TOML formula string into a `Formula`, returned as a JS object.
+pub fn parse_formula_impl(content: &str) -> Result<JsValue, JsValue> {
+    let json = match cache::parse_cache_get(content) {
+        Some(cached) => cached,
+        None => {
+            let formula: Formula = toml::from_str(content)
+                .map_err(|e| JsValue::from_str(&format!("TOML parse error: {}", e)))?;
+            let json = serde_json::to_string(&formula)
+                .map_err(|e| JsValue::from_str(&format!("Serialize error: {}", e)))?;
+            cache::parse_cache_put(content, json.clone());
+            json
+        }
+    };
+
+    js_sys::JSON::parse(&json)
+}
+
+/// Validate that `content` parses as a well-formed formula.
+pub fn validate_formula_impl(content: &str) -> bool {
+    toml::from_str::<Formula>(content).is_ok()
+}
+
+/// Get the `type` field of a TOML formula without building a full
+/// `Formula` value on the JS side.
+pub fn get_formula_type_impl(content: &str) -> Result<String, JsValue> {
+    let formula: Formula = toml::from_str(content)
+        .map_err(|e| JsValue::from_str(&format!("TOML parse error: {}", e)))?;
+
+    Ok(match formula.formula_type {
+        FormulaType::Convoy => "convoy",
+        FormulaType::Workflow => "workflow",
+        FormulaType::Expansion => "expansion",
+        FormulaType::Aspect => "aspect",
+    }
+    .to_string())
+}
@@ -3,8 +3,9 @@
 //! Directed Acyclic Graph operations for bead dependency management.
 
 use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
 use petgraph::graph::{DiGraph, NodeIndex};
-use petgraph::algo::is_cyclic_directed;
+use petgraph::algo::{is_cyclic_directed, toposort};
 use std::collections::{HashMap, HashSet, VecDeque};
 use crate::BeadNode;
 
@@ -81,6 +82,133 @@ pub fn compute_levels_impl(beads_json: &str) -> Result<String, JsValue> {
         .map_err(|e| JsValue::from_str(&format!("Serialize error: {}", e)))
 }
 
+/// Per-bead timing produced by the Critical Path Method.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CpmEntry {
+    pub id: String,
+    pub duration: u32,
+    pub earliest_start: u32,
+    pub earliest_finish: u32,
+    pub latest_start: u32,
+    pub latest_finish: u32,
+    pub slack: u32,
+}
+
+/// Result of [`compute_critical_path_impl`]: per-bead CPM timing plus the
+/// ordered chain of zero-slack beads that determines total completion time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CriticalPathResult {
+    pub beads: Vec<CpmEntry>,
+    pub project_duration: u32,
+    pub critical_path: Vec<String>,
+}
+
+/// Run the Critical Path Method over the bead dependency DAG.
+///
+/// Rejects cyclic input, then does a forward pass over a topological
+/// order computing `earliest_start`/`earliest_finish` from `duration`
+/// (missing durations treated as 0), a backward pass computing
+/// `latest_start`/`latest_finish` from the project duration, and derives
+/// `slack` as `latest_start - earliest_start`. The critical path is the
+/// ordered chain of zero-slack beads.
+pub fn compute_critical_path_impl(beads_json: &str) -> Result<String, JsValue> {
+    let beads: Vec<BeadNode> = serde_json::from_str(beads_json)
+        .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+
+    let graph = build_graph(&beads);
+    if is_cyclic_directed(&graph) {
+        return Err(JsValue::from_str("Cannot compute critical path: dependency graph has a cycle"));
+    }
+
+    let order = toposort(&graph, None)
+        .map_err(|_| JsValue::from_str("Cannot compute critical path: dependency graph has a cycle"))?;
+
+    let duration: HashMap<String, u32> = beads
+        .iter()
+        .map(|b| (b.id.clone(), b.duration.unwrap_or(0)))
+        .collect();
+    let blocked_by: HashMap<String, Vec<String>> = beads
+        .iter()
+        .map(|b| (b.id.clone(), b.blocked_by.clone()))
+        .collect();
+    let blocks: HashMap<String, Vec<String>> = beads
+        .iter()
+        .map(|b| (b.id.clone(), b.blocks.clone()))
+        .collect();
+
+    let ordered_ids: Vec<String> = order.iter().map(|&idx| graph[idx].clone()).collect();
+
+    // Forward pass: earliest_start/earliest_finish in topological order.
+    let mut earliest_start: HashMap<String, u32> = HashMap::new();
+    let mut earliest_finish: HashMap<String, u32> = HashMap::new();
+    for id in &ordered_ids {
+        let es = blocked_by[id]
+            .iter()
+            .filter_map(|u| earliest_finish.get(u))
+            .max()
+            .copied()
+            .unwrap_or(0);
+        let ef = es + duration[id];
+        earliest_start.insert(id.clone(), es);
+        earliest_finish.insert(id.clone(), ef);
+    }
+
+    let project_duration = earliest_finish.values().max().copied().unwrap_or(0);
+
+    // Backward pass: latest_finish/latest_start in reverse topological order.
+    let mut latest_start: HashMap<String, u32> = HashMap::new();
+    let mut latest_finish: HashMap<String, u32> = HashMap::new();
+    for id in ordered_ids.iter().rev() {
+        let lf = if blocks[id].is_empty() {
+            project_duration
+        } else {
+            blocks[id]
+                .iter()
+                .filter_map(|v| latest_start.get(v))
+                .min()
+                .copied()
+                .unwrap_or(project_duration)
+        };
+        let ls = lf.saturating_sub(duration[id]);
+        latest_finish.insert(id.clone(), lf);
+        latest_start.insert(id.clone(), ls);
+    }
+
+    let mut cpm_beads: Vec<CpmEntry> = ordered_ids
+        .iter()
+        .map(|id| {
+            let es = earliest_start[id];
+            let ls = latest_start[id];
+            CpmEntry {
+                id: id.clone(),
+                duration: duration[id],
+                earliest_start: es,
+                earliest_finish: earliest_finish[id],
+                latest_start: ls,
+                latest_finish: latest_finish[id],
+                slack: ls.saturating_sub(es),
+            }
+        })
+        .collect();
+
+    // Critical path: zero-slack beads, in the order they execute.
+    cpm_beads.sort_by_key(|b| b.earliest_start);
+    let critical_path: Vec<String> = cpm_beads
+        .iter()
+        .filter(|b| b.slack == 0)
+        .map(|b| b.id.clone())
+        .collect();
+
+    let result = CriticalPathResult {
+        beads: cpm_beads,
+        project_duration,
+        critical_path,
+    };
+
+    serde_json::to_string(&result)
+        .map_err(|e| JsValue::from_str(&format!("Serialize error: {}", e)))
+}
+
 /// Build a petgraph DiGraph from beads
 pub fn build_graph(beads: &[BeadNode]) -> DiGraph<String, ()> {
     let mut graph: DiGraph<String, ()> = DiGraph::new();
@@ -328,4 +456,76 @@ mod tests {
         assert_eq!(ready.len(), 1);
         assert_eq!(ready[0], "b");
     }
+
+    fn bead_with_duration(id: &str, blocked_by: Vec<&str>, blocks: Vec<&str>, duration: u32) -> BeadNode {
+        BeadNode {
+            id: id.to_string(),
+            title: id.to_string(),
+            status: "open".to_string(),
+            priority: 0,
+            blocked_by: blocked_by.into_iter().map(String::from).collect(),
+            blocks: blocks.into_iter().map(String::from).collect(),
+            duration: Some(duration),
+        }
+    }
+
+    // The error path constructs a `JsValue`, which wasm-bindgen only
+    // backs on an actual wasm32 target (see the similar note in
+    // gastown-formula-wasm/src/lib.rs).
+    #[test]
+    #[cfg(target_arch = "wasm32")]
+    fn test_critical_path_rejects_cycles() {
+        let beads = vec![
+            bead_with_duration("a", vec!["b"], vec!["b"], 1),
+            bead_with_duration("b", vec!["a"], vec!["a"], 1),
+        ];
+        let beads_json = serde_json::to_string(&beads).unwrap();
+        assert!(compute_critical_path_impl(&beads_json).is_err());
+    }
+
+    #[test]
+    fn test_critical_path_diamond() {
+        // a(2) -> b(3) -> d(1)
+        // a(2) -> c(1) -> d(1)
+        // critical chain is a -> b -> d (slack 0); c has slack.
+        let beads = vec![
+            bead_with_duration("a", vec![], vec!["b", "c"], 2),
+            bead_with_duration("b", vec!["a"], vec!["d"], 3),
+            bead_with_duration("c", vec!["a"], vec!["d"], 1),
+            bead_with_duration("d", vec!["b", "c"], vec![], 1),
+        ];
+        let beads_json = serde_json::to_string(&beads).unwrap();
+        let result = compute_critical_path_impl(&beads_json).unwrap();
+        let parsed: CriticalPathResult = serde_json::from_str(&result).unwrap();
+
+        assert_eq!(parsed.project_duration, 6);
+        assert_eq!(parsed.critical_path, vec!["a".to_string(), "b".to_string(), "d".to_string()]);
+
+        let by_id: HashMap<String, &CpmEntry> = parsed.beads.iter().map(|b| (b.id.clone(), b)).collect();
+        assert_eq!(by_id["a"].slack, 0);
+        assert_eq!(by_id["b"].slack, 0);
+        assert_eq!(by_id["c"].slack, 2);
+        assert_eq!(by_id["d"].slack, 0);
+        assert_eq!(by_id["d"].earliest_finish, 6);
+    }
+
+    #[test]
+    fn test_critical_path_treats_missing_duration_as_zero() {
+        let beads = vec![
+            BeadNode {
+                id: "a".to_string(),
+                title: "A".to_string(),
+                status: "open".to_string(),
+                priority: 0,
+                blocked_by: vec![],
+                blocks: vec![],
+                duration: None,
+            },
+        ];
+        let beads_json = serde_json::to_string(&beads).unwrap();
+        let result = compute_critical_path_impl(&beads_json).unwrap();
+        let parsed: CriticalPathResult = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed.project_duration, 0);
+        assert_eq!(parsed.beads[0].duration, 0);
+    }
 }
@@ -0,0 +1,125 @@
+//! Ruvector GNN WASM Module
+//!
+//! DAG utilities for Gas Town bead dependency graphs: cycle detection,
+//! adjacency, readiness, parallel execution levels, and critical-path
+//! scheduling.
+
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+mod dag;
+
+pub use dag::*;
+
+/// A single bead (unit of work) in the dependency graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeadNode {
+    pub id: String,
+    pub title: String,
+    pub status: String,
+    pub priority: i32,
+    #[serde(default)]
+    pub blocked_by: Vec<String>,
+    #[serde(default)]
+    pub blocks: Vec<String>,
+    #[serde(default)]
+    pub duration: Option<u32>,
+}
+
+/// Initialize the WASM module
+#[wasm_bindgen(start)]
+pub fn init() {
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+}
+
+/// Check if the dependency graph has cycles
+#[wasm_bindgen]
+pub fn has_cycle(beads_json: &str) -> Result<bool, JsValue> {
+    dag::has_cycle_impl(beads_json)
+}
+
+/// Find nodes that are part of cycles
+#[wasm_bindgen]
+pub fn find_cycle_nodes(beads_json: &str) -> Result<String, JsValue> {
+    dag::find_cycle_nodes_impl(beads_json)
+}
+
+/// Build adjacency list from beads
+#[wasm_bindgen]
+pub fn build_adjacency(beads_json: &str) -> Result<String, JsValue> {
+    dag::build_adjacency_impl(beads_json)
+}
+
+/// Get beads with no unresolved dependencies (ready to work on)
+#[wasm_bindgen]
+pub fn get_ready_beads(beads_json: &str) -> Result<String, JsValue> {
+    dag::get_ready_beads_impl(beads_json)
+}
+
+/// Compute execution levels (beads at same level can run in parallel)
+#[wasm_bindgen]
+pub fn compute_levels(beads_json: &str) -> Result<String, JsValue> {
+    dag::compute_levels_impl(beads_json)
+}
+
+/// Compute the schedule-critical path (CPM) over the bead DAG
+///
+/// # Arguments
+/// * `beads_json` - Beads as JSON string
+///
+/// # Returns
+/// * `String` - JSON with per-bead earliest/latest start/finish, slack, and the ordered critical-path id list
+#[wasm_bindgen]
+pub fn compute_critical_path(beads_json: &str) -> Result<String, JsValue> {
+    dag::compute_critical_path_impl(beads_json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bead(id: &str, blocked_by: Vec<&str>, blocks: Vec<&str>) -> BeadNode {
+        BeadNode {
+            id: id.to_string(),
+            title: id.to_string(),
+            status: "open".to_string(),
+            priority: 0,
+            blocked_by: blocked_by.into_iter().map(String::from).collect(),
+            blocks: blocks.into_iter().map(String::from).collect(),
+            duration: None,
+        }
+    }
+
+    #[test]
+    fn test_has_cycle_binding() {
+        let beads = vec![bead("a", vec![], vec![])];
+        let beads_json = serde_json::to_string(&beads).unwrap();
+        assert!(!has_cycle(&beads_json).unwrap());
+    }
+
+    #[test]
+    fn test_get_ready_beads_binding() {
+        let beads = vec![bead("a", vec![], vec!["b"]), bead("b", vec!["a"], vec![])];
+        let beads_json = serde_json::to_string(&beads).unwrap();
+        let result = get_ready_beads(&beads_json).unwrap();
+        let ready: Vec<String> = serde_json::from_str(&result).unwrap();
+        assert_eq!(ready, vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn test_compute_levels_binding() {
+        let beads = vec![bead("a", vec![], vec!["b"]), bead("b", vec!["a"], vec![])];
+        let beads_json = serde_json::to_string(&beads).unwrap();
+        let result = compute_levels(&beads_json).unwrap();
+        assert!(result.contains("\"a\""));
+    }
+
+    #[test]
+    fn test_compute_critical_path_binding() {
+        let beads = vec![bead("a", vec![], vec!["b"]), bead("b", vec!["a"], vec![])];
+        let beads_json = serde_json::to_string(&beads).unwrap();
+        let result = compute_critical_path(&beads_json).unwrap();
+        assert!(result.contains("critical_path"));
+    }
+}